@@ -0,0 +1,125 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use wasm_smith::{Config, Module as SmithModule};
+use wasmi::tracer::Tracer;
+use wasmi::{ImportsBuilder, ModuleInstance, NopExternals};
+
+/// Restrict wasm-smith to the subset of features the tracer actually
+/// understands, so a crash here is a real tracer bug and not just an
+/// unsupported-proposal rejection. Reference types and bulk-memory stay
+/// disabled: the tracer only has setup-time plumbing for them so far (see
+/// chunk0-1), not real `table.init`/`table.copy`/`table.grow`/`table.size`
+/// event tracing, so generating those ops would either produce false
+/// positives or exercise nothing new.
+struct TracedConfig;
+
+impl Config for TracedConfig {
+    fn reference_types_enabled(&self) -> bool {
+        false
+    }
+
+    fn bulk_memory_enabled(&self) -> bool {
+        false
+    }
+
+    fn simd_enabled(&self) -> bool {
+        false
+    }
+
+    fn threads_enabled(&self) -> bool {
+        false
+    }
+
+    fn exceptions_enabled(&self) -> bool {
+        false
+    }
+
+    fn max_memory_pages(&self, _is_64: bool) -> u64 {
+        // Keep generated modules cheap to trace.
+        16
+    }
+}
+
+fuzz_target!(|seed: &[u8]| {
+    let mut u = arbitrary::Unstructured::new(seed);
+    let module = match SmithModule::new(TracedConfig, &mut u) {
+        Ok(module) => module,
+        Err(_) => return,
+    };
+
+    let wasm_bytes = module.to_bytes();
+    let wasmi_module = match wasmi::Module::from_buffer(&wasm_bytes) {
+        Ok(module) => module,
+        Err(_) => return,
+    };
+
+    let instance = match ModuleInstance::new(&wasmi_module, &ImportsBuilder::default()) {
+        Ok(instance) => instance,
+        Err(_) => return,
+    };
+    let _ = instance.run_start(&mut NopExternals);
+
+    // Whether the module traps during its start function or runs clean, the
+    // tracer is still expected to have produced internally-consistent
+    // tables for everything executed up to that point.
+    let tracer = wasmi::tracer::tracer();
+    let tracer = tracer.borrow();
+    check_invariants(&tracer);
+
+    // The push_frame/pop_frame stack must be balanced by the time execution
+    // has unwound back to the top level, whether that's a clean return or a
+    // trap: pop_frame panics on underflow, so a non-zero depth here means a
+    // push was never matched by a pop rather than the stack going negative.
+    assert_eq!(
+        tracer.frame_depth(),
+        0,
+        "push_frame/pop_frame stack is unbalanced: depth {} at top level",
+        tracer.frame_depth(),
+    );
+});
+
+fn check_invariants(tracer: &Tracer) {
+    let etable_entries: Vec<_> = tracer.etable.entries().collect();
+
+    // Every etable entry's last_jump_eid, other than 0, must name an eid that
+    // exists and comes strictly before the entry itself.
+    for entry in &etable_entries {
+        assert!(
+            entry.last_jump_eid == 0
+                || (entry.last_jump_eid < entry.eid
+                    && etable_entries.iter().any(|e| e.eid == entry.last_jump_eid)),
+            "etable entry {} has last_jump_eid {} which does not refer to an existing, \
+             earlier etable entry",
+            entry.eid,
+            entry.last_jump_eid,
+        );
+    }
+
+    // Every (fid, iid) referenced by an etable entry must resolve to a real
+    // instruction table entry.
+    for entry in tracer.etable.entries() {
+        let resolved = tracer
+            .itable
+            .entries()
+            .iter()
+            .any(|ientry| ientry.fid == entry.fid && ientry.iid as u32 == entry.iid);
+        assert!(
+            resolved,
+            "etable entry {} references (fid={}, iid={}) which is not in the itable",
+            entry.eid, entry.fid, entry.iid,
+        );
+    }
+
+    // function_index_translation must cover every function index referenced
+    // by a `call` instruction anywhere in the itable.
+    for ientry in tracer.itable.entries() {
+        if let Some(callee) = ientry.called_function_index() {
+            assert!(
+                tracer.function_index_translation().contains_key(&callee),
+                "call target {} missing from function_index_translation",
+                callee,
+            );
+        }
+    }
+}
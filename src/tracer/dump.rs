@@ -0,0 +1,210 @@
+use std::fmt;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use specs::brtable::ElemTable;
+use specs::configure_table::ConfigureTable;
+use specs::etable::EventTable;
+use specs::itable::InstructionTable;
+use specs::jtable::JumpTable;
+use specs::jtable::StaticFrameEntry;
+
+use super::imtable::IMTable;
+use super::Tracer;
+
+/// Schema version of [`TraceDump`], bumped whenever the shape of the dumped
+/// tables changes in a way that is not backward compatible.
+pub const TRACE_DUMP_SCHEMA_VERSION: u32 = 1;
+
+/// A complete, self-contained snapshot of a finished [`Tracer`] run.
+///
+/// This is the artifact handed from trace generation (running a module in
+/// wasmi with tracing enabled) to an offline prover, so the two steps no
+/// longer need to happen in the same process.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TraceDump {
+    pub schema_version: u32,
+    pub itable: InstructionTable,
+    pub imtable: IMTable,
+    pub etable: EventTable,
+    pub jtable: JumpTable,
+    pub elem_table: ElemTable,
+    pub configure_table: ConfigureTable,
+    pub static_jtable_entries: Vec<StaticFrameEntry>,
+}
+
+/// Error loading a [`TraceDump`] that isn't on the schema version this build
+/// of wasmi knows how to read.
+#[derive(Debug)]
+pub struct SchemaVersionMismatch {
+    pub found: u32,
+    pub expected: u32,
+}
+
+impl fmt::Display for SchemaVersionMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "TraceDump schema version {} does not match the version this build expects ({})",
+            self.found, self.expected,
+        )
+    }
+}
+
+impl std::error::Error for SchemaVersionMismatch {}
+
+/// Error loading a [`TraceDump`] from JSON: either the bytes don't decode, or
+/// they decode into a dump whose schema version this build doesn't support.
+#[derive(Debug)]
+pub enum TraceDumpJsonError {
+    Decode(serde_json::Error),
+    SchemaVersion(SchemaVersionMismatch),
+}
+
+impl fmt::Display for TraceDumpJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TraceDumpJsonError::Decode(e) => write!(f, "{}", e),
+            TraceDumpJsonError::SchemaVersion(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for TraceDumpJsonError {}
+
+/// Error loading a [`TraceDump`] from bincode: either the bytes don't
+/// decode, or they decode into a dump whose schema version this build
+/// doesn't support.
+#[derive(Debug)]
+pub enum TraceDumpBincodeError {
+    Decode(bincode::Error),
+    SchemaVersion(SchemaVersionMismatch),
+}
+
+impl fmt::Display for TraceDumpBincodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TraceDumpBincodeError::Decode(e) => write!(f, "{}", e),
+            TraceDumpBincodeError::SchemaVersion(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for TraceDumpBincodeError {}
+
+impl TraceDump {
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, TraceDumpJsonError> {
+        let dump: Self = serde_json::from_str(json).map_err(TraceDumpJsonError::Decode)?;
+        dump.check_schema_version()
+            .map_err(TraceDumpJsonError::SchemaVersion)?;
+        Ok(dump)
+    }
+
+    pub fn to_bincode(&self) -> bincode::Result<Vec<u8>> {
+        bincode::serialize(self)
+    }
+
+    pub fn from_bincode(bytes: &[u8]) -> Result<Self, TraceDumpBincodeError> {
+        let dump: Self =
+            bincode::deserialize(bytes).map_err(TraceDumpBincodeError::Decode)?;
+        dump.check_schema_version()
+            .map_err(TraceDumpBincodeError::SchemaVersion)?;
+        Ok(dump)
+    }
+
+    fn check_schema_version(&self) -> Result<(), SchemaVersionMismatch> {
+        if self.schema_version == TRACE_DUMP_SCHEMA_VERSION {
+            Ok(())
+        } else {
+            Err(SchemaVersionMismatch {
+                found: self.schema_version,
+                expected: TRACE_DUMP_SCHEMA_VERSION,
+            })
+        }
+    }
+}
+
+impl Tracer {
+    /// Bundle the whole trace into a single, serializable [`TraceDump`].
+    pub fn export(&self) -> TraceDump {
+        TraceDump {
+            schema_version: TRACE_DUMP_SCHEMA_VERSION,
+            itable: self.itable.clone(),
+            imtable: self.imtable.clone(),
+            etable: self.etable.clone(),
+            jtable: self.jtable.clone(),
+            elem_table: self.elem_table.clone(),
+            configure_table: self.configure_table.clone(),
+            static_jtable_entries: self.static_jtable_entries.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_dump() -> TraceDump {
+        TraceDump {
+            schema_version: TRACE_DUMP_SCHEMA_VERSION,
+            itable: InstructionTable::default(),
+            imtable: IMTable::default(),
+            etable: EventTable::default(),
+            jtable: JumpTable::default(),
+            elem_table: ElemTable::default(),
+            configure_table: ConfigureTable::default(),
+            static_jtable_entries: vec![],
+        }
+    }
+
+    #[test]
+    fn json_round_trips() {
+        let dump = sample_dump();
+        let json = dump.to_json().unwrap();
+        let loaded = TraceDump::from_json(&json).unwrap();
+        assert_eq!(loaded.schema_version, dump.schema_version);
+    }
+
+    #[test]
+    fn bincode_round_trips() {
+        let dump = sample_dump();
+        let bytes = dump.to_bincode().unwrap();
+        let loaded = TraceDump::from_bincode(&bytes).unwrap();
+        assert_eq!(loaded.schema_version, dump.schema_version);
+    }
+
+    #[test]
+    fn json_rejects_schema_version_mismatch() {
+        let mut dump = sample_dump();
+        dump.schema_version = 0;
+        let json = dump.to_json().unwrap();
+
+        match TraceDump::from_json(&json) {
+            Err(TraceDumpJsonError::SchemaVersion(e)) => {
+                assert_eq!(e.found, 0);
+                assert_eq!(e.expected, TRACE_DUMP_SCHEMA_VERSION);
+            }
+            other => panic!("expected a schema version mismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn bincode_rejects_schema_version_mismatch() {
+        let mut dump = sample_dump();
+        dump.schema_version = 0;
+        let bytes = dump.to_bincode().unwrap();
+
+        match TraceDump::from_bincode(&bytes) {
+            Err(TraceDumpBincodeError::SchemaVersion(e)) => {
+                assert_eq!(e.found, 0);
+                assert_eq!(e.expected, TRACE_DUMP_SCHEMA_VERSION);
+            }
+            other => panic!("expected a schema version mismatch, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use specs::mtable::VarType;
+
+/// One initial-memory or global entry, covering the inclusive cell range
+/// `[start, end]`. `is_global` distinguishes a global entry (where `start`
+/// and `end` are both the global index) from a linear-memory entry (where
+/// they mark a run of equal-valued `u64` cells).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IMTableEntry {
+    pub is_global: bool,
+    pub is_mutable: bool,
+    pub start: u32,
+    pub end: u32,
+    pub vtype: VarType,
+    pub value: u64,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct IMTable {
+    entries: Vec<IMTableEntry>,
+}
+
+impl IMTable {
+    pub fn push(
+        &mut self,
+        is_global: bool,
+        is_mutable: bool,
+        start: u32,
+        end: u32,
+        vtype: VarType,
+        value: u64,
+    ) {
+        self.entries.push(IMTableEntry {
+            is_global,
+            is_mutable,
+            start,
+            end,
+            vtype,
+            value,
+        });
+    }
+
+    pub fn entries(&self) -> &[IMTableEntry] {
+        &self.entries
+    }
+}
@@ -1,4 +1,6 @@
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::convert::TryInto;
 
 use regex::Regex;
 use specs::brtable::ElemEntry;
@@ -26,10 +28,62 @@ use self::etable::ETable;
 use self::imtable::IMTable;
 use self::phantom::PhantomFunction;
 
+pub mod dump;
 pub mod etable;
 pub mod imtable;
 pub mod phantom;
 
+/// Sentinel `funcref`/`externref` index used to represent a null reference.
+///
+/// Real function indices are allocated starting at `1` (see
+/// `allocate_func_index`), so `u32::MAX` can never collide with one and a
+/// null reference resolved by `lookup_function_or_null` is not mistaken for
+/// function index `0`. `lookup_type_of_func_ref` takes a non-null `FuncRef`
+/// and has no case for a null reference.
+pub const NULL_FUNC_INDEX: u32 = u32::MAX;
+
+/// Why a `table.init`/`table.copy` was rejected by an up-front bounds check.
+///
+/// Both instructions must validate the whole access before writing a single
+/// entry, so a trap can never happen after a partial copy.
+///
+/// NOTE: this is bounds-check plumbing only. Nothing in this crate calls
+/// `check_table_init`/`check_table_copy` yet — that requires a `table.init`/
+/// `table.copy` execution path, which isn't part of this tree.
+/// `#[allow(dead_code)]` here mirrors
+/// `statistics_instructions` below.
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TableAccessError {
+    OutOfBounds,
+    SegmentDropped,
+}
+
+/// Which direction a `table.copy` must walk its range in. Per the bulk-memory
+/// spec, overlapping source/destination ranges within the same table must
+/// behave as if copied through a temporary buffer: forward when `dst <= src`,
+/// backward otherwise, so an overlapping copy never reads back a value it
+/// just wrote. Unused until `table.copy` execution lands; see
+/// `TableAccessError` above.
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CopyDirection {
+    Forward,
+    Backward,
+}
+
+/// Index width of a table, as introduced by the memory64-era `table64`
+/// proposal. A `table64` table addresses its entries (and the operands of
+/// `table.get`/`table.set`/`table.init`/`table.copy`/`table.grow`) with
+/// `i64` rather than `i32`, so offsets no longer fit in a `u32`. Unused until
+/// a real table64 flag is threaded in; see `push_table_index_type`.
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TableIndexType {
+    I32,
+    I64,
+}
+
 #[derive(Debug)]
 pub struct FuncDesc {
     pub index_within_jtable: u32,
@@ -45,6 +99,7 @@ pub struct Tracer {
     pub jtable: JumpTable,
     pub elem_table: ElemTable,
     pub configure_table: ConfigureTable,
+    pub(crate) dropped_elem_segments: HashSet<u32>,
     type_of_func_ref: Vec<(FuncRef, u32)>,
     function_lookup: Vec<(FuncRef, u32)>,
     pub(crate) last_jump_eid: Vec<u32>,
@@ -73,6 +128,7 @@ impl Tracer {
             jtable: JumpTable::default(),
             elem_table: ElemTable::default(),
             configure_table: ConfigureTable::default(),
+            dropped_elem_segments: HashSet::default(),
             type_of_func_ref: vec![],
             function_lookup: vec![],
             function_index_allocator: 1,
@@ -98,6 +154,18 @@ impl Tracer {
         *self.last_jump_eid.last().unwrap()
     }
 
+    /// Depth of the `push_frame`/`pop_frame` stack. Zero once execution has
+    /// unwound back to the top level, since `pop_frame` panics on underflow
+    /// rather than letting the stack go negative.
+    pub fn frame_depth(&self) -> usize {
+        self.last_jump_eid.len()
+    }
+
+    /// Map from a module's function index to its [`FuncDesc`].
+    pub fn function_index_translation(&self) -> &HashMap<u32, FuncDesc> {
+        &self.function_index_translation
+    }
+
     pub fn eid(&self) -> u32 {
         self.etable.get_latest_eid()
     }
@@ -120,11 +188,54 @@ impl Tracer {
     pub(crate) fn push_init_memory(&mut self, memref: MemoryRef) {
         let pages = (*memref).limits().initial();
         // one page contains 64KB*1024/8=8192 u64 entries
-        for i in 0..(pages * 8192) {
-            let mut buf = [0u8; 8];
-            (*memref).get_into(i * 8, &mut buf).unwrap();
+        let cells = pages * 8192;
+
+        // Scan the memory through a fixed-size window (one page at a time)
+        // instead of either a get_into call per 8-byte cell (the per-call
+        // overhead dominated startup) or one buffer sized to the whole
+        // memory (up to ~4GB for a max-size 32-bit memory, doubling peak
+        // memory on top of whatever memref already holds internally).
+        const WINDOW_CELLS: u32 = 8192;
+        let mut window = vec![0u8; WINDOW_CELLS as usize * 8];
+
+        // Coalesce maximal runs of equal consecutive u64 cells into a single
+        // range entry instead of emitting one entry per cell: real linear
+        // memories are almost entirely zero at instantiation, so this turns
+        // a zeroed multi-megabyte memory into a handful of entries rather
+        // than one per 8 bytes.
+        let mut run_start = 0;
+        let mut run_value = None;
+        let mut i = 0;
+
+        while i < cells {
+            let window_cells = WINDOW_CELLS.min(cells - i);
+            let window_bytes = &mut window[..window_cells as usize * 8];
+            (*memref).get_into(i * 8, window_bytes).unwrap();
+
+            for chunk in window_bytes.chunks_exact(8) {
+                let value = u64::from_le_bytes(chunk.try_into().unwrap());
+
+                match run_value {
+                    None => {
+                        run_start = i;
+                        run_value = Some(value);
+                    }
+                    Some(v) if v == value => {}
+                    Some(v) => {
+                        self.imtable
+                            .push(false, true, run_start, i - 1, VarType::I64, v);
+                        run_start = i;
+                        run_value = Some(value);
+                    }
+                }
+
+                i = i + 1;
+            }
+        }
+
+        if let Some(v) = run_value {
             self.imtable
-                .push(false, true, i, i, VarType::I64, u64::from_le_bytes(buf));
+                .push(false, true, run_start, cells - 1, VarType::I64, v);
         }
 
         self.imtable.push(
@@ -154,19 +265,157 @@ impl Tracer {
         );
     }
 
-    pub(crate) fn push_elem(&mut self, table_idx: u32, offset: u32, func_idx: u32, type_idx: u32) {
+    /// Record one `(table_idx, offset) -> func_idx` entry of an element
+    /// segment. `segment_id` identifies the segment the entry came from and
+    /// `is_passive` records whether that segment is active (placed at
+    /// instantiation time) or passive (only materialized by `table.init`).
+    /// `func_ref` is resolved through [`Tracer::lookup_function_or_null`] so
+    /// a `ref.null` entry is recorded as [`NULL_FUNC_INDEX`] rather than a
+    /// real function index.
+    pub(crate) fn push_elem(
+        &mut self,
+        table_idx: u32,
+        offset: u64,
+        func_ref: Option<&FuncRef>,
+        type_idx: u32,
+        segment_id: u32,
+        is_passive: bool,
+    ) {
+        let func_idx = self.lookup_function_or_null(func_ref);
+
         self.elem_table.insert(ElemEntry {
             table_idx,
             type_idx,
             offset,
             func_idx,
+            segment_id,
+            is_passive,
         })
     }
 
+    /// Record whether `table_idx` is addressed with `i32` or `i64` offsets,
+    /// so consumers of the trace know how wide the `table.*` operands for
+    /// that table are.
+    ///
+    /// Unused until the module parser threads a real table64 flag through
+    /// to the caller in `register_module_instance`.
+    #[allow(dead_code)]
+    pub(crate) fn push_table_index_type(&mut self, table_idx: u32, index_type: TableIndexType) {
+        self.configure_table
+            .set_table_index_type(table_idx, index_type == TableIndexType::I64);
+    }
+
+    /// Mark a passive element segment as dropped by `elem.drop`. A dropped
+    /// segment's entries stay in the `elem_table` for provenance but may no
+    /// longer be the source of a `table.init`.
+    ///
+    /// Unused until `elem.drop` execution lands; see `TableAccessError`.
+    #[allow(dead_code)]
+    pub(crate) fn elem_drop(&mut self, segment_id: u32) {
+        self.dropped_elem_segments.insert(segment_id);
+    }
+
+    pub(crate) fn is_elem_segment_dropped(&self, segment_id: u32) -> bool {
+        self.dropped_elem_segments.contains(&segment_id)
+    }
+
+    /// Validate a `table.init segment_id table_idx` up front: `[src, src+len)`
+    /// must fit in the segment and `[dst, dst+len)` in the table, so the
+    /// write can proceed knowing it will never trap partway through.
+    ///
+    /// A dropped segment is treated as having length `0`, matching the spec:
+    /// a zero-length `table.init` at `src == 0` against a dropped (or
+    /// never-populated) segment is a no-op and succeeds as long as `dst` is
+    /// within the table, while any non-zero-length access to a dropped
+    /// segment fails with `SegmentDropped`.
+    ///
+    /// Unused until `table.init` execution lands; see `TableAccessError`.
+    #[allow(dead_code)]
+    pub(crate) fn check_table_init(
+        &self,
+        segment_id: u32,
+        segment_len: u64,
+        table_len: u64,
+        dst: u64,
+        src: u64,
+        len: u64,
+    ) -> Result<(), TableAccessError> {
+        let dropped = self.is_elem_segment_dropped(segment_id);
+        let segment_len = if dropped { 0 } else { segment_len };
+
+        let dst_end = dst.checked_add(len).ok_or(TableAccessError::OutOfBounds)?;
+        let src_end = src.checked_add(len).ok_or(TableAccessError::OutOfBounds)?;
+        if dst_end > table_len || src_end > segment_len {
+            return Err(if dropped && len > 0 {
+                TableAccessError::SegmentDropped
+            } else {
+                TableAccessError::OutOfBounds
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Validate a `table.copy` up front: `[src, src+len)` and `[dst, dst+len)`
+    /// must both fit in their (possibly distinct) tables, so the copy can
+    /// proceed knowing it will never trap partway through.
+    ///
+    /// Unused until `table.copy` execution lands; see `TableAccessError`.
+    #[allow(dead_code)]
+    pub(crate) fn check_table_copy(
+        &self,
+        dst_table_len: u64,
+        src_table_len: u64,
+        dst: u64,
+        src: u64,
+        len: u64,
+    ) -> Result<(), TableAccessError> {
+        let dst_end = dst.checked_add(len).ok_or(TableAccessError::OutOfBounds)?;
+        let src_end = src.checked_add(len).ok_or(TableAccessError::OutOfBounds)?;
+        if dst_end > dst_table_len || src_end > src_table_len {
+            return Err(TableAccessError::OutOfBounds);
+        }
+
+        Ok(())
+    }
+
+    /// Direction a (bounds-checked) `table.copy` must walk in so an
+    /// overlapping range within the same table copies correctly.
+    ///
+    /// Unused until `table.copy` execution lands; see `TableAccessError`.
+    #[allow(dead_code)]
+    pub(crate) fn table_copy_direction(dst: u64, src: u64) -> CopyDirection {
+        if dst <= src {
+            CopyDirection::Forward
+        } else {
+            CopyDirection::Backward
+        }
+    }
+
     pub(crate) fn push_type_of_func_ref(&mut self, func: FuncRef, type_idx: u32) {
         self.type_of_func_ref.push((func, type_idx))
     }
 
+    /// Resolve a (possibly null) `funcref`/`externref` to the index used in
+    /// the trace, for `ref.null` / `ref.func` / `ref.is_null`. A `None`
+    /// reference resolves to [`NULL_FUNC_INDEX`] rather than `0`, so it can
+    /// never collide with a real function index.
+    pub(crate) fn lookup_function_or_null(&self, func_ref: Option<&FuncRef>) -> u32 {
+        match func_ref {
+            Some(func_ref) => self.lookup_function(func_ref),
+            None => NULL_FUNC_INDEX,
+        }
+    }
+
+    /// `ref.is_null` on an index already resolved by
+    /// [`Tracer::lookup_function_or_null`].
+    ///
+    /// Unused until `ref.is_null` execution lands; see `TableAccessError`.
+    #[allow(dead_code)]
+    pub(crate) fn is_null_ref(func_idx: u32) -> bool {
+        func_idx == NULL_FUNC_INDEX
+    }
+
     #[allow(dead_code)]
     pub(crate) fn statistics_instructions<'a>(&mut self, module_instance: &ModuleRef) {
         let mut func_index = 0;
@@ -273,6 +522,16 @@ impl Tracer {
             }
         }
 
+        // `push_table_index_type` intentionally isn't called here yet. A
+        // table's declared `maximum` is stored as `u32` on `TableDescriptor`
+        // in this crate, so "does the maximum overflow u32" can never tell
+        // a table64 table apart from a regular one — every table would be
+        // recorded as `TableIndexType::I32`, which is worse than recording
+        // nothing: a downstream prover would trust a wrong width instead of
+        // an absent one. Calling this for real needs `TableDescriptor` (the
+        // module parser, not part of this tree) to carry the table's actual
+        // index-type bit.
+
         {
             let phantom_functions = self.phantom_functions.clone();
 